@@ -12,10 +12,10 @@
 
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode, header},
+    extract::{DefaultBodyLimit, FromRequestParts, Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode, header, request::Parts},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{get, patch, post},
 };
 use bcrypt::{DEFAULT_COST, hash, verify};
 use chrono::{Duration, Utc};
@@ -26,11 +26,81 @@ use std::{
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{error, info};
+use tower_http::trace::TraceLayer;
+use tracing::{Span, error, field, info, info_span};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
 use validator::Validate;
 
+// ============================================================================
+// PUBLIC IDS - Short, opaque identifiers exposed in URLs and responses
+// ============================================================================
+/// Codec that maps between an internal [`Uuid`] and the short alphanumeric
+/// string shown to clients.
+///
+/// The 128-bit id is split into two `u64` halves and run through `sqids`, so
+/// the mapping is fully reversible without keeping a lookup table around.
+mod public_id {
+    use sqids::Sqids;
+    use uuid::Uuid;
+
+    /// Encode a `Uuid` as its short public form.
+    pub fn encode(id: &Uuid) -> String {
+        let n = id.as_u128();
+        let parts = [(n >> 64) as u64, n as u64];
+        // `encode` only fails on the reserved blocklist, which our two
+        // numeric halves cannot trigger; fall back to the raw UUID if it ever does.
+        Sqids::default()
+            .encode(&parts)
+            .unwrap_or_else(|_| id.to_string())
+    }
+
+    /// Decode a short public id back into its `Uuid`, rejecting any string
+    /// that is not a canonical encoding.
+    pub fn decode(s: &str) -> Option<Uuid> {
+        let sqids = Sqids::default();
+        let parts = sqids.decode(s);
+        if parts.len() != 2 {
+            return None;
+        }
+        // Guard against non-canonical inputs that happen to decode.
+        if sqids.encode(&parts).ok().as_deref() != Some(s) {
+            return None;
+        }
+        let n = ((parts[0] as u128) << 64) | parts[1] as u128;
+        Some(Uuid::from_u128(n))
+    }
+
+    /// `serialize_with` helper: emit a `Uuid` field as its short public id.
+    pub fn serialize<S>(id: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&encode(id))
+    }
+}
+
+/// A `Uuid` parsed from its short public form, for use in `Path<PublicId>`.
+pub struct PublicId(pub Uuid);
+
+impl<'de> Deserialize<'de> for PublicId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        public_id::decode(&s)
+            .map(PublicId)
+            .ok_or_else(|| serde::de::Error::custom("invalid id"))
+    }
+}
+
 // ============================================================================
 // MODELS - The User and Post models
 // ============================================================================
@@ -42,11 +112,68 @@ pub struct User {
     #[serde(skip_serializing)]
     pub hashed_password: String,
     pub created_at: i64,
+    #[serde(default)]
+    pub avatar_id: Option<Uuid>,
+    #[serde(default)]
+    pub status: UserStatus,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Lifecycle state of an account. A `Blocked` user can neither authenticate
+/// nor act until an admin restores it, without the record being deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    #[default]
+    Active,
+    Blocked,
+}
+
+/// A decoded, re-encoded image held in memory, served by `GET /images/:id`.
+///
+/// Uploads are always normalized to a canonical format, so `content_type` is
+/// derived from the stored encoding rather than the untrusted upload.
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub bytes: Vec<u8>,
+    pub content_type: String,
+}
+
+/// A capability an API key may be granted, gating individual endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Action {
+    #[serde(rename = "posts.read")]
+    PostsRead,
+    #[serde(rename = "posts.write")]
+    PostsWrite,
+    #[serde(rename = "posts.delete")]
+    PostsDelete,
+    #[serde(rename = "keys.manage")]
+    KeysManage,
+}
+
+/// A scoped API key for machine clients.
+///
+/// The `id` is the non-secret, listable handle. The secret sent in the
+/// `X-Api-Key` header is shown only once at creation; only its hash is kept
+/// here, so reading a key never leaks a usable credential.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Key {
+    pub id: Uuid,
+    pub name: String,
+    pub actions: Vec<Action>,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// Bcrypt hash of the secret; never serialized to clients.
+    #[serde(skip)]
+    pub key_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Post {
+    #[serde(serialize_with = "public_id::serialize")]
     pub id: Uuid,
+    #[serde(serialize_with = "public_id::serialize")]
     pub user_id: Uuid,
     pub title: String,
     pub content: String,
@@ -58,7 +185,7 @@ pub struct Post {
 // ============================================================================
 // `Validate` trait: Axum will automatically check these rules before
 // the handler runs. If validation fails, returns 400 Bad Request.
-#[derive(Debug, Validate, Deserialize)]
+#[derive(Debug, Validate, Deserialize, ToSchema)]
 pub struct SignupRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
@@ -68,25 +195,40 @@ pub struct SignupRequest {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
     #[validate(email)]
     pub email: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
     pub token: String,
+    pub refresh_token: String,
     pub user: UserResponse,
 }
 
-#[derive(Debug, Serialize)]
+/// Body of `POST /auth/refresh` and `POST /auth/logout`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// A freshly minted access token.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
+    #[serde(serialize_with = "public_id::serialize")]
     pub id: Uuid,
     pub email: String,
     pub username: String,
     pub created_at: i64,
+    pub avatar_id: Option<Uuid>,
 }
 
 impl From<User> for UserResponse {
@@ -96,11 +238,12 @@ impl From<User> for UserResponse {
             email: user.email,
             username: user.username,
             created_at: user.created_at,
+            avatar_id: user.avatar_id,
         }
     }
 }
 
-#[derive(Debug, Validate, Deserialize)]
+#[derive(Debug, Validate, Deserialize, ToSchema)]
 pub struct CreatePostRequest {
     #[validate(length(min = 1, max = 200))]
     pub title: String,
@@ -108,13 +251,84 @@ pub struct CreatePostRequest {
     pub content: String,
 }
 
-/// Pagination query parameters
+/// Partial update for a post; only the supplied fields are changed.
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct UpdatePostRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub title: Option<String>,
+    #[validate(length(min = 1, max = 5000))]
+    pub content: Option<String>,
+}
+
+/// Body of `POST /keys`.
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct CreateKeyRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub actions: Vec<Action>,
+    pub expires_at: Option<i64>,
+}
+
+/// Response to `POST /keys`: the stored key plus its plaintext secret, which
+/// is shown only here and never again.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedKeyResponse {
+    #[serde(flatten)]
+    pub inner: Key,
+    /// The secret to send in `X-Api-Key`. Store it now; it cannot be recovered.
+    pub key: String,
+}
+
+/// Partial update for a key; mirrors Meilisearch's `patch_api_key`, which only
+/// lets the human-facing `name` change — never the granted `actions`.
+#[derive(Debug, Validate, Deserialize, ToSchema)]
+pub struct UpdateKeyRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+}
+
+/// Pagination, search and filtering query parameters for `GET /posts`.
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
     #[serde(default = "default_page")]
     pub page: usize,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Free-text search; split on whitespace, matched with AND semantics
+    /// against the lowercased title and content.
+    pub q: Option<String>,
+    /// Restrict to posts owned by this user, given as the public id that
+    /// responses expose.
+    pub author: Option<String>,
+    /// Sort by `created_at`: `"asc"` or `"desc"` (default).
+    pub sort: Option<String>,
+    /// Opaque keyset cursor from a previous page's `next_cursor`. When present,
+    /// the response switches to cursor mode instead of offset slicing.
+    pub after: Option<String>,
+}
+
+// ============================================================================
+// KEYSET CURSORS - Opaque `(created_at, id)` markers for stable pagination
+// ============================================================================
+/// Encode/decode the opaque base64 cursor carrying the `(created_at, id)` of
+/// the last post a client has seen.
+mod cursor {
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+    use uuid::Uuid;
+
+    /// Encode a `(created_at, id)` pair into its opaque cursor string.
+    pub fn encode(created_at: i64, id: &Uuid) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{created_at}:{id}"))
+    }
+
+    /// Decode a cursor string back into `(created_at, id)`, returning `None`
+    /// for any malformed input.
+    pub fn decode(s: &str) -> Option<(i64, Uuid)> {
+        let raw = URL_SAFE_NO_PAD.decode(s).ok()?;
+        let text = String::from_utf8(raw).ok()?;
+        let (ts, id) = text.split_once(':')?;
+        Some((ts.parse().ok()?, Uuid::parse_str(id).ok()?))
+    }
 }
 
 fn default_page() -> usize {
@@ -125,7 +339,7 @@ fn default_limit() -> usize {
 }
 
 /// Paginated response wrapper
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
     pub page: usize,
@@ -133,6 +347,16 @@ pub struct PaginatedResponse<T> {
     pub total: usize,
 }
 
+/// Cursor-paginated response wrapper. `next_cursor` is `None` when fewer than
+/// `limit` posts were returned; a full page always carries a cursor, even if
+/// no posts follow it, in which case the next request returns an empty page.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CursorResponse<T> {
+    pub data: Vec<T>,
+    pub limit: usize,
+    pub next_cursor: Option<String>,
+}
+
 // ============================================================================
 // JWT - What we encode in the authentication token
 // ============================================================================
@@ -141,6 +365,26 @@ pub struct Claims {
     pub sub: String, // Subject (user ID)
     pub email: String,
     pub exp: usize,
+    /// Token kind: `"access"` (default) or `"refresh"`.
+    #[serde(default = "default_token_type")]
+    pub typ: String,
+    /// Refresh-token id; only present on refresh tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<Uuid>,
+}
+
+fn default_token_type() -> String {
+    "access".to_string()
+}
+
+/// A live refresh token, keyed by its `jti` in `AppState`.
+///
+/// Removing the record (logout) immediately invalidates the session even
+/// though the signed JWT itself is still within its validity window.
+#[derive(Debug, Clone)]
+pub struct RefreshRecord {
+    pub user_id: Uuid,
+    pub exp: i64,
 }
 
 // ============================================================================
@@ -158,7 +402,11 @@ pub struct AppState {
     pub users: Arc<DashMap<Uuid, User>>,
     pub posts: Arc<DashMap<Uuid, Post>>,
     pub email_index: Arc<DashMap<String, Uuid>>, // Quick Lookup by Email
+    pub refresh_tokens: Arc<DashMap<Uuid, RefreshRecord>>, // Live refresh tokens by `jti`
+    pub images: Arc<DashMap<Uuid, StoredImage>>,           // Uploaded images by id
+    pub keys: Arc<DashMap<Uuid, Key>>,                     // Scoped API keys by id
     pub jwt_secret: String,
+    pub admin_email: Option<String>, // Privileged account allowed to block/unblock users
 }
 
 // ============================================================================
@@ -169,6 +417,7 @@ pub enum ApiError {
     InvalidCredentials,
     UserAlreadyExists,
     Unauthorized,
+    AccountBlocked,
     NotFound,
     ValidationError(String),
     InternalError(String),
@@ -184,6 +433,7 @@ impl IntoResponse for ApiError {
             ApiError::InvalidCredentials => (StatusCode::UNAUTHORIZED, "Invalid credentials"),
             ApiError::UserAlreadyExists => (StatusCode::CONFLICT, "User already exists"),
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
+            ApiError::AccountBlocked => (StatusCode::FORBIDDEN, "Account is blocked"),
             ApiError::NotFound => (StatusCode::NOT_FOUND, "Not Found"),
             ApiError::ValidationError(msg) => {
                 return (
@@ -214,9 +464,10 @@ impl IntoResponse for ApiError {
 // JWT UTILITIES - Token creation and validation
 // ============================================================================
 
+/// Mint a short-lived access token (15 minutes) carrying the user's claims.
 pub fn create_token(user_id: &Uuid, email: &str, secret: &str) -> Result<String, ApiError> {
     let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+        .checked_add_signed(Duration::minutes(15))
         .ok_or_else(|| ApiError::InternalError("Failed to calculate expiration".into()))?
         .timestamp() as usize;
 
@@ -224,6 +475,8 @@ pub fn create_token(user_id: &Uuid, email: &str, secret: &str) -> Result<String,
         sub: user_id.to_string(),
         email: email.to_string(),
         exp: expiration,
+        typ: "access".to_string(),
+        jti: None,
     };
 
     encode(
@@ -234,6 +487,50 @@ pub fn create_token(user_id: &Uuid, email: &str, secret: &str) -> Result<String,
     .map_err(|e| ApiError::InternalError(format!("Token Creation failed: {}", e)))
 }
 
+/// Mint a long-lived refresh token (30 days) with a random `jti`.
+///
+/// Returns the encoded token together with the `jti` and expiry so the caller
+/// can record it in `AppState::refresh_tokens` for later revocation.
+pub fn create_refresh_token(
+    user_id: &Uuid,
+    email: &str,
+    secret: &str,
+) -> Result<(String, Uuid, i64), ApiError> {
+    let exp = Utc::now()
+        .checked_add_signed(Duration::days(30))
+        .ok_or_else(|| ApiError::InternalError("Failed to calculate expiration".into()))?
+        .timestamp();
+
+    let jti = Uuid::new_v4();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        email: email.to_string(),
+        exp: exp as usize,
+        typ: "refresh".to_string(),
+        jti: Some(jti),
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(|e| ApiError::InternalError(format!("Token Creation failed: {}", e)))?;
+
+    Ok((token, jti, exp))
+}
+
+/// Decode and signature-check a raw JWT, returning its claims.
+fn decode_claims(token: &str, secret: &str) -> Result<Claims, ApiError> {
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+    .map_err(|_| ApiError::Unauthorized)
+}
+
 pub fn validate_token(headers: &HeaderMap, secret: &str) -> Result<Claims, ApiError> {
     let auth_header = headers
         .get(header::AUTHORIZATION)
@@ -247,13 +544,96 @@ pub fn validate_token(headers: &HeaderMap, secret: &str) -> Result<Claims, ApiEr
 
     let token = &auth_header[7..];
 
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
-    .map_err(|_| ApiError::Unauthorized)
+    let claims = decode_claims(token, secret)?;
+
+    // Refresh tokens are only accepted by `/auth/refresh`, never on
+    // access-protected routes.
+    if claims.typ == "refresh" {
+        return Err(ApiError::Unauthorized);
+    }
+
+    Ok(claims)
+}
+
+/// Resolve and authorize a scoped API key from the `X-Api-Key` header.
+///
+/// Parallels [`validate_token`]: it looks the key up, rejects expired keys,
+/// and requires the key to carry `required` before the caller may proceed.
+pub fn validate_api_key(
+    headers: &HeaderMap,
+    state: &AppState,
+    required: Action,
+) -> Result<Key, ApiError> {
+    let raw = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(ApiError::Unauthorized)?;
+
+    // The secret is `<id>.<random>`; the `id` prefix locates the record whose
+    // stored hash the full secret is then verified against.
+    let id_part = raw
+        .split_once('.')
+        .map(|(id, _)| id)
+        .ok_or(ApiError::Unauthorized)?;
+    let id = Uuid::parse_str(id_part).map_err(|_| ApiError::Unauthorized)?;
+    let key = state.keys.get(&id).ok_or(ApiError::Unauthorized)?;
+
+    if verify(raw, &key.key_hash) != Ok(true) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    if let Some(expires_at) = key.expires_at {
+        if Utc::now().timestamp() > expires_at {
+            return Err(ApiError::Unauthorized);
+        }
+    }
+
+    if !key.actions.contains(&required) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    Ok(key.clone())
+}
+
+// ============================================================================
+// EXTRACTORS - Request-part extractors shared across handlers
+// ============================================================================
+
+/// The authenticated user, resolved from the `Authorization` header.
+///
+/// Runs `validate_token` + user lookup once so protected handlers can take a
+/// `user: AuthUser` parameter instead of repeating the header plumbing.
+/// Derefs to the loaded `User` for ergonomic field access.
+pub struct AuthUser(pub User);
+
+impl std::ops::Deref for AuthUser {
+    type Target = User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = validate_token(&parts.headers, &state.jwt_secret)?;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
+        let user = state.users.get(&user_id).ok_or(ApiError::NotFound)?;
+
+        // A blocked account cannot act, even with an otherwise valid token.
+        if user.status == UserStatus::Blocked {
+            return Err(ApiError::AccountBlocked);
+        }
+
+        Span::current().record("principal", field::display(user.id));
+
+        Ok(AuthUser(user.clone()))
+    }
 }
 
 // ============================================================================
@@ -271,6 +651,17 @@ async fn health_check() -> Json<serde_json::Value> {
 
 /// POST /auth/signup
 /// Body: { "email": "...", "username": "...", "password": "..." }
+#[utoipa::path(
+    post,
+    path = "/auth/signup",
+    request_body = SignupRequest,
+    responses(
+        (status = 200, description = "Account created", body = AuthResponse),
+        (status = 400, description = "Validation error"),
+        (status = 409, description = "User already exists"),
+    ),
+    tag = "auth"
+)]
 async fn signup(
     State(state): State<AppState>,
     Json(payload): Json<SignupRequest>,
@@ -292,23 +683,46 @@ async fn signup(
         username: payload.username,
         hashed_password: hashed_password?,
         created_at: Utc::now().timestamp(),
+        avatar_id: None,
+        status: UserStatus::Active,
     };
 
     let token = create_token(&user.id, &user.email, &state.jwt_secret)?;
+    let (refresh_token, jti, exp) =
+        create_refresh_token(&user.id, &user.email, &state.jwt_secret)?;
 
     state.email_index.insert(user.email.clone(), user.id);
     state.users.insert(user.id, user.clone());
+    state.refresh_tokens.insert(
+        jti,
+        RefreshRecord {
+            user_id: user.id,
+            exp,
+        },
+    );
 
     info!("New user registered: {}", user.email);
 
     Ok(Json(AuthResponse {
         token,
+        refresh_token,
         user: user.into(),
     }))
 }
 
 /// POST /auth/login
 /// Body: { "email": "...", "password": "..." }
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Authenticated", body = AuthResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Invalid credentials"),
+    ),
+    tag = "auth"
+)]
 async fn login(
     State(state): State<AppState>,
     Json(payload): Json<LoginRequest>,
@@ -336,49 +750,321 @@ async fn login(
         return Err(ApiError::InvalidCredentials);
     }
 
-    // Generate token
+    // A blocked account may hold valid credentials but must not get a session.
+    if user.status == UserStatus::Blocked {
+        return Err(ApiError::AccountBlocked);
+    }
+
+    // Generate tokens
     let token = create_token(&user.id, &user.email, &state.jwt_secret)?;
+    let (refresh_token, jti, exp) =
+        create_refresh_token(&user.id, &user.email, &state.jwt_secret)?;
+
+    state.refresh_tokens.insert(
+        jti,
+        RefreshRecord {
+            user_id: user.id,
+            exp,
+        },
+    );
 
     info!("User logged in: {}", user.email);
 
     Ok(Json(AuthResponse {
         token,
+        refresh_token,
         user: user.clone().into(),
     }))
 }
 
+/// POST /auth/refresh
+/// Body: { "refresh_token": "..." }
+///
+/// Exchanges a still-live refresh token for a fresh access token.
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New access token", body = TokenResponse),
+        (status = 401, description = "Refresh token invalid or revoked"),
+        (status = 404, description = "User not found"),
+    ),
+    tag = "auth"
+)]
+async fn refresh(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>, ApiError> {
+    let claims = decode_claims(&payload.refresh_token, &state.jwt_secret)?;
+
+    if claims.typ != "refresh" {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let jti = claims.jti.ok_or(ApiError::Unauthorized)?;
+
+    // A revoked (logged-out) token is gone from the store even if still
+    // within its signed validity window.
+    if !state.refresh_tokens.contains_key(&jti) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
+    let user = state.users.get(&user_id).ok_or(ApiError::NotFound)?;
+
+    // A user blocked after login must not be able to mint fresh access tokens.
+    if user.status == UserStatus::Blocked {
+        return Err(ApiError::AccountBlocked);
+    }
+
+    let token = create_token(&user.id, &user.email, &state.jwt_secret)?;
+
+    Ok(Json(TokenResponse { token }))
+}
+
+/// POST /auth/logout
+/// Body: { "refresh_token": "..." }
+///
+/// Revokes the refresh token so it can no longer be exchanged.
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    request_body = RefreshRequest,
+    responses(
+        (status = 204, description = "Refresh token revoked"),
+        (status = 401, description = "Malformed token"),
+    ),
+    tag = "auth"
+)]
+async fn logout(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<StatusCode, ApiError> {
+    let claims = decode_claims(&payload.refresh_token, &state.jwt_secret)?;
+
+    if let Some(jti) = claims.jti {
+        state.refresh_tokens.remove(&jti);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// GET /users/me
 /// Headers: Authorization: Bearer <token>
-async fn get_current_user(
+#[utoipa::path(
+    get,
+    path = "/users/me",
+    responses(
+        (status = 200, description = "Current user", body = UserResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found"),
+    ),
+    tag = "users"
+)]
+async fn get_current_user(user: AuthUser) -> Result<Json<UserResponse>, ApiError> {
+    Ok(Json(user.0.into()))
+}
+
+/// Ensure the caller is the configured admin account.
+///
+/// Returns `Unauthorized` when no admin is configured or the caller is anyone
+/// else, so the block/unblock routes are inert unless `ADMIN_EMAIL` is set.
+fn require_admin(user: &User, state: &AppState) -> Result<(), ApiError> {
+    match &state.admin_email {
+        Some(email) if *email == user.email => Ok(()),
+        _ => Err(ApiError::Unauthorized),
+    }
+}
+
+/// Set the lifecycle `status` of the user at `id`, shared by block/unblock.
+fn set_user_status(
+    state: &AppState,
+    admin: &User,
+    id: Uuid,
+    status: UserStatus,
+) -> Result<StatusCode, ApiError> {
+    require_admin(admin, state)?;
+
+    let mut user = state.users.get_mut(&id).ok_or(ApiError::NotFound)?;
+    user.status = status;
+
+    // Blocking must take effect immediately: drop every live refresh token for
+    // this user so they cannot exchange one for a fresh access token.
+    if status == UserStatus::Blocked {
+        state.refresh_tokens.retain(|_, record| record.user_id != id);
+    }
+
+    info!("User {} set to {:?} by {}", id, status, admin.email);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /users/:id/block
+/// Headers: Authorization: Bearer <admin token>
+async fn block_user(
     State(state): State<AppState>,
-    headers: HeaderMap,
-) -> Result<Json<UserResponse>, ApiError> {
-    let claims = validate_token(&headers, &state.jwt_secret)?;
-    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
+    admin: AuthUser,
+    Path(PublicId(id)): Path<PublicId>,
+) -> Result<StatusCode, ApiError> {
+    set_user_status(&state, &admin, id, UserStatus::Blocked)
+}
 
-    let user = state.users.get(&user_id).ok_or(ApiError::NotFound)?;
+/// POST /users/:id/unblock
+/// Headers: Authorization: Bearer <admin token>
+async fn unblock_user(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(PublicId(id)): Path<PublicId>,
+) -> Result<StatusCode, ApiError> {
+    set_user_status(&state, &admin, id, UserStatus::Active)
+}
+
+/// Maximum accepted upload size, before decoding.
+const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+/// Longest edge of a stored image; larger uploads are scaled down.
+const MAX_IMAGE_DIM: u32 = 512;
 
-    Ok(Json(user.clone().into()))
+/// POST /users/me/avatar
+/// Headers: Authorization: Bearer <token>
+/// Body: multipart/form-data with a single image field
+///
+/// Oversized payloads are rejected before buffering by the route's
+/// `DefaultBodyLimit` (sized to `MAX_UPLOAD_BYTES`); the remaining bytes are
+/// decoded, non-images rejected, scaled down to `MAX_IMAGE_DIM`, and re-encoded
+/// to PNG so all metadata from the untrusted upload is stripped.
+async fn upload_avatar(
+    State(state): State<AppState>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|_| ApiError::ValidationError("Invalid multipart body".into()))?
+        .ok_or_else(|| ApiError::ValidationError("No file provided".into()))?;
+
+    let data = field
+        .bytes()
+        .await
+        .map_err(|_| ApiError::ValidationError("Failed to read upload".into()))?;
+
+    if data.len() > MAX_UPLOAD_BYTES {
+        return Err(ApiError::ValidationError("Image too large".into()));
+    }
+
+    let image = image::load_from_memory(&data)
+        .map_err(|_| ApiError::ValidationError("Not a valid image".into()))?;
+
+    // Only shrink: images already within bounds keep their size.
+    let normalized = if image.width() > MAX_IMAGE_DIM || image.height() > MAX_IMAGE_DIM {
+        image.resize(
+            MAX_IMAGE_DIM,
+            MAX_IMAGE_DIM,
+            image::imageops::FilterType::Lanczos3,
+        )
+    } else {
+        image
+    };
+
+    let mut buf = std::io::Cursor::new(Vec::new());
+    normalized
+        .write_to(&mut buf, image::ImageFormat::Png)
+        .map_err(|e| ApiError::InternalError(format!("Image encoding failed: {}", e)))?;
+
+    let id = Uuid::new_v4();
+    let content_type = mime_guess::from_ext("png")
+        .first_or_octet_stream()
+        .to_string();
+
+    state.images.insert(
+        id,
+        StoredImage {
+            bytes: buf.into_inner(),
+            content_type,
+        },
+    );
+
+    // Point the user's avatar at the freshly stored image.
+    if let Some(mut stored) = state.users.get_mut(&user.id) {
+        stored.avatar_id = Some(id);
+    }
+
+    info!("Avatar uploaded: {} by user {}", id, user.id);
+
+    Ok(Json(serde_json::json!({ "image_id": id })))
+}
+
+/// GET /images/:id
+/// Serves a stored image with its canonical `Content-Type`.
+async fn get_image(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let image = state.images.get(&id).ok_or(ApiError::NotFound)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, image.content_type.clone())],
+        image.bytes.clone(),
+    )
+        .into_response())
+}
+
+/// Resolve the id acting on a posts endpoint, from either a user JWT or an
+/// `X-Api-Key`.
+///
+/// A bearer token is preferred when present (and subject to the blocked-account
+/// check); otherwise an API key is accepted, provided it carries `required`.
+fn authorize_posts(
+    headers: &HeaderMap,
+    state: &AppState,
+    required: Action,
+) -> Result<Uuid, ApiError> {
+    if headers.contains_key(header::AUTHORIZATION) {
+        let claims = validate_token(headers, &state.jwt_secret)?;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
+        let user = state.users.get(&user_id).ok_or(ApiError::NotFound)?;
+        if user.status == UserStatus::Blocked {
+            return Err(ApiError::AccountBlocked);
+        }
+        Span::current().record("principal", field::display(user_id));
+        Ok(user_id)
+    } else {
+        let key = validate_api_key(headers, state, required)?;
+        Span::current().record("principal", field::display(key.id));
+        Ok(key.id)
+    }
 }
 
 /// POST /posts
-/// Headers: Authorization: Bearer <token>
+/// Headers: Authorization: Bearer <token> or X-Api-Key: <key>
 /// Body: { "title": "...", "content": "..." }
+#[utoipa::path(
+    post,
+    path = "/posts",
+    request_body = CreatePostRequest,
+    responses(
+        (status = 201, description = "Post created", body = Post),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "posts"
+)]
 async fn create_post(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<CreatePostRequest>,
 ) -> Result<(StatusCode, Json<Post>), ApiError> {
+    let actor = authorize_posts(&headers, &state, Action::PostsWrite)?;
+
     payload
         .validate()
         .map_err(|e| ApiError::ValidationError(e.to_string()))?;
 
-    let claims = validate_token(&headers, &state.jwt_secret)?;
-    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
-
     let post = Post {
         id: Uuid::new_v4(),
-        user_id,
+        user_id: actor,
         title: payload.title,
         content: payload.content,
         created_at: Utc::now().timestamp(),
@@ -386,24 +1072,85 @@ async fn create_post(
 
     state.posts.insert(post.id, post.clone());
 
-    info!("Post created: {} by user {}", post.id, user_id);
+    info!("Post created: {} by {}", post.id, actor);
 
     Ok((StatusCode::CREATED, Json(post)))
 }
 
-/// GET /posts?page=1&limit=10
+/// GET /posts?page=1&limit=10&q=...&author=...&sort=asc
+#[utoipa::path(
+    get,
+    path = "/posts",
+    responses(
+        (status = 200, description = "Paginated, optionally filtered posts"),
+    ),
+    tag = "posts"
+)]
 async fn get_posts(
     State(state): State<AppState>,
     Query(params): Query<PaginationParams>,
-) -> Json<PaginatedResponse<Post>> {
+) -> Response {
+    // Pre-split the search terms once so every post is checked cheaply.
+    let terms: Vec<String> = params
+        .q
+        .as_deref()
+        .map(|q| q.to_lowercase().split_whitespace().map(String::from).collect())
+        .unwrap_or_default();
+
+    // `author` arrives as a public id; decode it to the internal UUID. An
+    // unparseable value decodes to nil and therefore matches nothing.
+    let author = params
+        .author
+        .as_deref()
+        .map(|a| public_id::decode(a).unwrap_or_else(Uuid::nil));
+
     let mut posts: Vec<Post> = state
         .posts
         .iter()
         .map(|entry| entry.value().clone())
+        .filter(|post| author.is_none_or(|author| post.user_id == author))
+        .filter(|post| {
+            if terms.is_empty() {
+                return true;
+            }
+            let haystack = format!("{} {}", post.title, post.content).to_lowercase();
+            terms.iter().all(|term| haystack.contains(term))
+        })
         .collect();
 
-    // Sort by creation date (newest first)
-    posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    // Cursor mode: keyset over `(created_at, id)` descending, stable under
+    // concurrent inserts/deletes. Opt in by supplying `after`.
+    if params.after.is_some() {
+        posts.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+
+        // A malformed cursor yields no lower bound, so it is ignored.
+        if let Some((ts, id)) = params.after.as_deref().and_then(cursor::decode) {
+            posts.retain(|post| (post.created_at, post.id) < (ts, id));
+        }
+
+        posts.truncate(params.limit);
+        let next_cursor = (posts.len() == params.limit)
+            .then(|| posts.last().map(|p| cursor::encode(p.created_at, &p.id)))
+            .flatten();
+
+        return Json(CursorResponse {
+            data: posts,
+            limit: params.limit,
+            next_cursor,
+        })
+        .into_response();
+    }
+
+    // Sort by creation date; newest first unless `sort=asc` is requested.
+    if params.sort.as_deref() == Some("asc") {
+        posts.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    } else {
+        posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    }
 
     let total = posts.len();
     let start = (params.page.saturating_sub(1)) * params.limit;
@@ -421,12 +1168,23 @@ async fn get_posts(
         limit: params.limit,
         total,
     })
+    .into_response()
 }
 
 /// GET /posts/:id
+#[utoipa::path(
+    get,
+    path = "/posts/{id}",
+    params(("id" = String, Path, description = "Short public post id")),
+    responses(
+        (status = 200, description = "The post", body = Post),
+        (status = 404, description = "Post not found"),
+    ),
+    tag = "posts"
+)]
 async fn get_post(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
+    Path(PublicId(id)): Path<PublicId>,
 ) -> Result<Json<Post>, ApiError> {
     let post = state.posts.get(&id).ok_or(ApiError::NotFound)?;
 
@@ -434,39 +1192,335 @@ async fn get_post(
 }
 
 /// DELETE /posts/:id
-/// Headers: Authorization: Bearer <token>
+/// Headers: Authorization: Bearer <token> or X-Api-Key: <key>
+#[utoipa::path(
+    delete,
+    path = "/posts/{id}",
+    params(("id" = String, Path, description = "Short public post id")),
+    responses(
+        (status = 204, description = "Post deleted"),
+        (status = 401, description = "Not the owner"),
+        (status = 404, description = "Post not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "posts"
+)]
 async fn delete_post(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Path(id): Path<Uuid>,
+    Path(PublicId(id)): Path<PublicId>,
 ) -> Result<StatusCode, ApiError> {
-    let claims = validate_token(&headers, &state.jwt_secret)?;
-    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
+    let actor = authorize_posts(&headers, &state, Action::PostsDelete)?;
 
     let post = state.posts.get(&id).ok_or(ApiError::NotFound)?;
 
     // Check ownership
-    if post.user_id != user_id {
+    if post.user_id != actor {
         return Err(ApiError::Unauthorized);
     }
 
     state.posts.remove(&id);
 
-    info!("Post deleted: {} by user {}", id, user_id);
+    info!("Post deleted: {} by {}", id, actor);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /posts/:id
+/// Headers: Authorization: Bearer <token>
+/// Body: { "title"?: "...", "content"?: "..." }
+///
+/// Applies only the supplied fields, leaving the rest of the post untouched.
+async fn update_post(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(PublicId(id)): Path<PublicId>,
+    Json(payload): Json<UpdatePostRequest>,
+) -> Result<Json<Post>, ApiError> {
+    payload
+        .validate()
+        .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+    let mut post = state.posts.get_mut(&id).ok_or(ApiError::NotFound)?;
+
+    // Check ownership
+    if post.user_id != user.id {
+        return Err(ApiError::Unauthorized);
+    }
+
+    if let Some(title) = payload.title {
+        post.title = title;
+    }
+    if let Some(content) = payload.content {
+        post.content = content;
+    }
+
+    info!("Post updated: {} by user {}", id, user.id);
+
+    Ok(Json(post.clone()))
+}
+
+// ============================================================================
+// API KEY HANDLERS - Scoped credentials for machine clients
+// ============================================================================
+
+/// Authorize a caller to manage API keys: the configured admin (via JWT) or a
+/// key that itself carries [`Action::KeysManage`].
+///
+/// Without this, any authenticated user could enumerate and revoke everyone
+/// else's keys, so key management is deliberately privileged.
+fn authorize_keys_manage(headers: &HeaderMap, state: &AppState) -> Result<(), ApiError> {
+    if headers.contains_key(header::AUTHORIZATION) {
+        let claims = validate_token(headers, &state.jwt_secret)?;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ApiError::Unauthorized)?;
+        let user = state.users.get(&user_id).ok_or(ApiError::NotFound)?;
+        if user.status == UserStatus::Blocked {
+            return Err(ApiError::AccountBlocked);
+        }
+        require_admin(user.value(), state)
+    } else {
+        validate_api_key(headers, state, Action::KeysManage).map(|_| ())
+    }
+}
+
+/// POST /keys
+/// Headers: Authorization: Bearer <admin token> or X-Api-Key: <keys.manage key>
+/// Body: { "name": "...", "actions": ["posts.read"], "expires_at": 123 }
+async fn create_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedKeyResponse>), ApiError> {
+    authorize_keys_manage(&headers, &state)?;
+
+    payload
+        .validate()
+        .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+    let id = Uuid::new_v4();
+    // Secret is `<id>.<random>`: the prefix locates the record on lookup, the
+    // random tail is the part that actually has to be guessed.
+    let secret = format!("{}.{}", id.simple(), Uuid::new_v4().simple());
+    let key_hash = hash(&secret, DEFAULT_COST)
+        .map_err(|e| ApiError::InternalError(format!("Key hashing failed: {}", e)))?;
+
+    let key = Key {
+        id,
+        name: payload.name,
+        actions: payload.actions,
+        created_at: Utc::now().timestamp(),
+        expires_at: payload.expires_at,
+        key_hash,
+    };
+
+    state.keys.insert(key.id, key.clone());
+
+    info!("API key created: {} ({})", key.id, key.name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedKeyResponse { inner: key, key: secret }),
+    ))
+}
+
+/// GET /keys?page=1&limit=10
+/// Headers: Authorization: Bearer <admin token> or X-Api-Key: <keys.manage key>
+async fn list_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<PaginatedResponse<Key>>, ApiError> {
+    authorize_keys_manage(&headers, &state)?;
+
+    let mut keys: Vec<Key> = state
+        .keys
+        .iter()
+        .map(|entry| entry.value().clone())
+        .collect();
+
+    keys.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let total = keys.len();
+    let start = (params.page.saturating_sub(1)) * params.limit;
+    let end = (start + params.limit).min(total);
+
+    let page = if start < total {
+        keys[start..end].to_vec()
+    } else {
+        vec![]
+    };
+
+    Ok(Json(PaginatedResponse {
+        data: page,
+        page: params.page,
+        limit: params.limit,
+        total,
+    }))
+}
+
+/// GET /keys/:id
+/// Headers: Authorization: Bearer <admin token> or X-Api-Key: <keys.manage key>
+async fn get_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Key>, ApiError> {
+    authorize_keys_manage(&headers, &state)?;
+
+    let key = state.keys.get(&id).ok_or(ApiError::NotFound)?;
+
+    Ok(Json(key.clone()))
+}
+
+/// PATCH /keys/:id
+/// Headers: Authorization: Bearer <admin token> or X-Api-Key: <keys.manage key>
+/// Body: { "name": "..." }
+async fn patch_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateKeyRequest>,
+) -> Result<Json<Key>, ApiError> {
+    authorize_keys_manage(&headers, &state)?;
+
+    payload
+        .validate()
+        .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+    let mut key = state.keys.get_mut(&id).ok_or(ApiError::NotFound)?;
+
+    if let Some(name) = payload.name {
+        key.name = name;
+    }
+
+    Ok(Json(key.clone()))
+}
+
+/// DELETE /keys/:id
+/// Headers: Authorization: Bearer <admin token> or X-Api-Key: <keys.manage key>
+async fn delete_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    authorize_keys_manage(&headers, &state)?;
+
+    state.keys.remove(&id).ok_or(ApiError::NotFound)?;
+
+    info!("API key revoked: {}", id);
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ============================================================================
+// OPENAPI - Machine-readable API description
+// ============================================================================
+/// The generated OpenAPI document, served at `/api-docs/openapi.json` and
+/// rendered by the Swagger UI mounted at `/swagger-ui`.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        signup,
+        login,
+        refresh,
+        logout,
+        get_current_user,
+        create_post,
+        get_posts,
+        get_post,
+        delete_post,
+    ),
+    components(schemas(
+        SignupRequest,
+        LoginRequest,
+        AuthResponse,
+        RefreshRequest,
+        TokenResponse,
+        UserResponse,
+        CreatePostRequest,
+        Post,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "auth", description = "Authentication and session management"),
+        (name = "users", description = "User accounts"),
+        (name = "posts", description = "Posts"),
+    )
+)]
+struct ApiDoc;
+
+/// Registers the `Authorization: Bearer` security scheme on the generated spec.
+struct SecurityAddon;
+
+impl utoipa::Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+
+        let components = openapi
+            .components
+            .get_or_insert_with(utoipa::openapi::Components::new);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+// ============================================================================
+// LOGGING - Non-blocking, file-rotated structured logging
+// ============================================================================
+
+/// Configure tracing to write to stdout and a rotated file through a
+/// non-blocking background worker, so logging never blocks the async handlers.
+///
+/// Tunable via env: `LOG_DIR` (default `logs`), `LOG_ROTATION`
+/// (`minutely`/`hourly`/`daily`/`never`, default `daily`), and `LOG_FORMAT`
+/// (`pretty`/`json`, default `pretty`). The returned guard must be kept alive
+/// for the lifetime of the process to flush buffered lines on shutdown.
+fn init_tracing() -> WorkerGuard {
+    let dir = std::env::var("LOG_DIR").unwrap_or_else(|_| "logs".to_string());
+    let rotation = match std::env::var("LOG_ROTATION").as_deref() {
+        Ok("minutely") => Rotation::MINUTELY,
+        Ok("hourly") => Rotation::HOURLY,
+        Ok("never") => Rotation::NEVER,
+        _ => Rotation::DAILY,
+    };
+    let json = matches!(std::env::var("LOG_FORMAT").as_deref(), Ok("json"));
+
+    let (file_writer, guard) =
+        tracing_appender::non_blocking(RollingFileAppender::new(rotation, dir, "api.log"));
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let registry = tracing_subscriber::registry().with(filter);
+    if json {
+        registry
+            .with(fmt::layer().json().with_writer(std::io::stdout))
+            .with(fmt::layer().json().with_ansi(false).with_writer(file_writer))
+            .init();
+    } else {
+        registry
+            .with(fmt::layer().with_target(false).compact().with_writer(std::io::stdout))
+            .with(fmt::layer().with_target(false).with_ansi(false).with_writer(file_writer))
+            .init();
+    }
+
+    guard
+}
+
 #[tokio::main]
 async fn main() {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .compact()
-        .init();
-
     dotenvy::dotenv().ok();
 
+    // Initialize logging (guard flushes the background writer on drop).
+    let _log_guard = init_tracing();
+
     // JWT Secret
     let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set!");
 
@@ -475,7 +1529,11 @@ async fn main() {
         users: Arc::new(DashMap::new()),
         posts: Arc::new(DashMap::new()),
         email_index: Arc::new(DashMap::new()),
+        refresh_tokens: Arc::new(DashMap::new()),
+        images: Arc::new(DashMap::new()),
+        keys: Arc::new(DashMap::new()),
         jwt_secret,
+        admin_email: std::env::var("ADMIN_EMAIL").ok(),
     };
 
     // Configure CORS
@@ -484,19 +1542,66 @@ async fn main() {
         .allow_methods(Any)
         .allow_headers(Any);
 
+    // Transparently compress responses (gzip/br) per the client's
+    // `Accept-Encoding`, shrinking large list and OpenAPI payloads.
+    let compression = CompressionLayer::new();
+
+    // Wrap every request in a span carrying method/path and, once the handler
+    // has run, the matched principal, status and latency. Handlers record the
+    // `principal` field via `Span::current()`.
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(|req: &axum::http::Request<_>| {
+            info_span!(
+                "request",
+                method = %req.method(),
+                path = %req.uri().path(),
+                principal = field::Empty,
+                status = field::Empty,
+                latency_ms = field::Empty,
+            )
+        })
+        .on_response(|res: &Response, latency: std::time::Duration, span: &Span| {
+            span.record("status", res.status().as_u16());
+            span.record("latency_ms", latency.as_millis() as u64);
+        });
+
     // Build the router
     let app = Router::new()
         // Public routes (no auth required)
         .route("/health", get(health_check))
         .route("/auth/signup", post(signup))
         .route("/auth/login", post(login))
+        .route("/auth/refresh", post(refresh))
+        .route("/auth/logout", post(logout))
         // Protected routes (auth required)
         .route("/users/me", get(get_current_user))
+        .route("/users/{id}/block", post(block_user))
+        .route("/users/{id}/unblock", post(unblock_user))
+        .route(
+            "/users/me/avatar",
+            // Bound the request body up front; this also overrides axum's 2 MiB
+            // default so it agrees with the 5 MiB `MAX_UPLOAD_BYTES` guard.
+            post(upload_avatar).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/images/{id}", get(get_image))
         .route("/posts", post(create_post).get(get_posts))
-        .route("/posts/{id}", get(get_post).delete(delete_post))
+        .route(
+            "/posts/{id}",
+            get(get_post).patch(update_post).delete(delete_post),
+        )
+        // Scoped API keys for machine clients
+        .route("/keys", post(create_key).get(list_keys))
+        .route(
+            "/keys/{id}",
+            get(get_key).patch(patch_key).delete(delete_key),
+        )
+        // Interactive API docs + machine-readable spec
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         // Add state and middleware
         .with_state(state)
-        .layer(cors);
+        .layer(cors)
+        .layer(compression)
+        .layer(trace);
 
     // Start server
     let addr = "0.0.0.0:3000";
@@ -507,11 +1612,25 @@ async fn main() {
     info!("  GET    /health           - Health check");
     info!("  POST   /auth/signup      - Create account");
     info!("  POST   /auth/login       - Login");
+    info!("  POST   /auth/refresh     - Exchange refresh token for access token");
+    info!("  POST   /auth/logout      - Revoke refresh token");
     info!("  GET    /users/me         - Get current user (auth)");
+    info!("  POST   /users/:id/block  - Block a user (admin)");
+    info!("  POST   /users/:id/unblock - Unblock a user (admin)");
+    info!("  POST   /users/me/avatar  - Upload avatar image (auth)");
+    info!("  GET    /images/:id       - Serve stored image");
     info!("  POST   /posts            - Create post (auth)");
     info!("  GET    /posts            - List posts (paginated)");
     info!("  GET    /posts/:id        - Get specific post");
+    info!("  PATCH  /posts/:id        - Update post (auth, owner only)");
     info!("  DELETE /posts/:id        - Delete post (auth, owner only)");
+    info!("  POST   /keys             - Create API key (auth)");
+    info!("  GET    /keys             - List API keys (auth, paginated)");
+    info!("  GET    /keys/:id         - Get API key (auth)");
+    info!("  PATCH  /keys/:id         - Update API key name (auth)");
+    info!("  DELETE /keys/:id         - Revoke API key (auth)");
+    info!("  GET    /api-docs/openapi.json - OpenAPI spec");
+    info!("  GET    /swagger-ui       - Swagger UI");
 
     axum::serve(listener, app).await.unwrap();
 }